@@ -76,6 +76,7 @@ impl<P: ProgressStore> IndexerExecutor<P> {
                 remote_store_url,
                 remote_store_options,
                 reader_options,
+                self.metrics.clone(),
             );
         spawn_monitored_task!(checkpoint_reader.run());
 
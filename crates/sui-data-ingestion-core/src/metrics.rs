@@ -1,11 +1,15 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use prometheus::{register_int_gauge_vec_with_registry, IntGaugeVec, Registry};
+use prometheus::{
+    register_int_counter_with_registry, register_int_gauge_vec_with_registry, IntCounter,
+    IntGaugeVec, Registry,
+};
 
 #[derive(Clone)]
 pub struct DataIngestionMetrics {
     pub data_ingestion_checkpoint: IntGaugeVec,
+    pub data_ingestion_remote_fetch_retries: IntCounter,
 }
 
 impl DataIngestionMetrics {
@@ -18,6 +22,12 @@ impl DataIngestionMetrics {
                 registry,
             )
             .unwrap(),
+            data_ingestion_remote_fetch_retries: register_int_counter_with_registry!(
+                "data_ingestion_remote_fetch_retries",
+                "Number of remote checkpoint fetches retried after a transient error.",
+                registry,
+            )
+            .unwrap(),
         }
     }
 }
@@ -3,6 +3,7 @@
 
 use crate::create_remote_store_client;
 use crate::executor::MAX_CHECKPOINTS_IN_PROGRESS;
+use crate::DataIngestionMetrics;
 use anyhow::Result;
 use backoff::backoff::Backoff;
 use futures::StreamExt;
@@ -13,6 +14,7 @@ use object_store::path::Path;
 use object_store::ObjectStore;
 use std::ffi::OsString;
 use std::fs;
+use std::future::Future;
 use std::path::PathBuf;
 use std::time::Duration;
 use std::{collections::BTreeMap, sync::Arc};
@@ -42,6 +44,7 @@ pub struct CheckpointReader {
     exit_receiver: oneshot::Receiver<()>,
     options: ReaderOptions,
     data_limiter: DataLimiter,
+    metrics: DataIngestionMetrics,
 }
 
 #[derive(Clone)]
@@ -54,6 +57,13 @@ pub struct ReaderOptions {
     pub upper_limit: Option<CheckpointSequenceNumber>,
     /// Whether to delete processed checkpoint files from the local directory.
     pub gc_checkpoint_files: bool,
+    /// Delay before retrying a failed remote fetch. It doubles, with jitter, on every further
+    /// failure of the same checkpoint, up to `retry_max_delay_ms`.
+    pub retry_base_delay_ms: u64,
+    pub retry_max_delay_ms: u64,
+    /// Number of failed attempts after which a remote fetch gives up. `None` keeps retrying until
+    /// the fetch deadline. Checkpoints that are not uploaded yet do not count as failures.
+    pub retry_max_attempts: Option<usize>,
 }
 
 impl Default for ReaderOptions {
@@ -65,10 +75,16 @@ impl Default for ReaderOptions {
             data_limit: 0,
             upper_limit: None,
             gc_checkpoint_files: true,
+            retry_base_delay_ms: 100,
+            retry_max_delay_ms: 10_000,
+            retry_max_attempts: None,
         }
     }
 }
 
+/// How long a remote fetch of a single checkpoint is retried before the fetcher is restarted.
+const REMOTE_FETCH_DEADLINE: Duration = Duration::from_secs(60);
+
 enum RemoteStore {
     ObjectStore(Box<dyn ObjectStore>),
     Rest(sui_rest_api::Client),
@@ -146,35 +162,73 @@ impl CheckpointReader {
     async fn remote_fetch_checkpoint(
         store: &RemoteStore,
         checkpoint_number: CheckpointSequenceNumber,
+        options: &ReaderOptions,
+        metrics: &DataIngestionMetrics,
     ) -> Result<(Arc<CheckpointData>, usize)> {
-        let mut backoff = backoff::ExponentialBackoff::default();
-        backoff.max_elapsed_time = Some(Duration::from_secs(60));
-        backoff.initial_interval = Duration::from_millis(100);
-        backoff.current_interval = backoff.initial_interval;
-        backoff.multiplier = 1.0;
+        Self::retry_remote_fetch(options, metrics, || {
+            Self::remote_fetch_checkpoint_internal(store, checkpoint_number)
+        })
+        .await
+    }
+
+    /// Runs `fetch` until it succeeds. Transient errors are retried with jittered exponential
+    /// backoff and counted in `data_ingestion_remote_fetch_retries`. A not-found error means the
+    /// checkpoint has not been uploaded yet, which is the normal state at the tip of the chain, so
+    /// it is polled again at the base delay without backing off or counting as a failure.
+    pub(crate) async fn retry_remote_fetch<T, F, Fut>(
+        options: &ReaderOptions,
+        metrics: &DataIngestionMetrics,
+        mut fetch: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let base_delay = Duration::from_millis(options.retry_base_delay_ms);
+        let mut backoff = backoff::ExponentialBackoff {
+            initial_interval: base_delay,
+            current_interval: base_delay,
+            max_interval: Duration::from_millis(options.retry_max_delay_ms),
+            multiplier: 2.0,
+            max_elapsed_time: Some(REMOTE_FETCH_DEADLINE),
+            ..Default::default()
+        };
+        let mut failures = 0;
         loop {
-            match Self::remote_fetch_checkpoint_internal(store, checkpoint_number).await {
+            let err = match fetch().await {
                 Ok(data) => return Ok(data),
-                Err(err) => match backoff.next_backoff() {
-                    Some(duration) => {
-                        if !err.to_string().contains("404") {
-                            debug!(
-                                "remote reader retry in {} ms. Error is {:?}",
-                                duration.as_millis(),
-                                err
-                            );
-                        }
-                        tokio::time::sleep(duration).await
-                    }
-                    None => return Err(err),
-                },
-            }
+                Err(err) => err,
+            };
+            let delay = if is_not_found(&err) {
+                if backoff.get_elapsed_time() >= REMOTE_FETCH_DEADLINE {
+                    return Err(err);
+                }
+                base_delay
+            } else {
+                failures += 1;
+                if matches!(options.retry_max_attempts, Some(max) if failures >= max) {
+                    return Err(err);
+                }
+                let Some(delay) = backoff.next_backoff() else {
+                    return Err(err);
+                };
+                metrics.data_ingestion_remote_fetch_retries.inc();
+                debug!(
+                    "remote reader retry in {} ms. Error is {:?}",
+                    delay.as_millis(),
+                    err
+                );
+                delay
+            };
+            tokio::time::sleep(delay).await
         }
     }
 
     fn start_remote_fetcher(&mut self) -> mpsc::Receiver<Result<(Arc<CheckpointData>, usize)>> {
         let batch_size = self.options.batch_size;
         let start_checkpoint = self.current_checkpoint_number;
+        let options = self.options.clone();
+        let metrics = self.metrics.clone();
         let (sender, receiver) = mpsc::channel(batch_size);
         let url = self
             .remote_store_url
@@ -202,7 +256,9 @@ impl CheckpointReader {
 
         spawn_monitored_task!(async move {
             let mut checkpoint_stream = (start_checkpoint..u64::MAX)
-                .map(|checkpoint_number| Self::remote_fetch_checkpoint(&store, checkpoint_number))
+                .map(|checkpoint_number| {
+                    Self::remote_fetch_checkpoint(&store, checkpoint_number, &options, &metrics)
+                })
                 .pipe(futures::stream::iter)
                 .buffered(batch_size);
 
@@ -322,6 +378,7 @@ impl CheckpointReader {
         remote_store_url: Option<String>,
         remote_store_options: Vec<(String, String)>,
         options: ReaderOptions,
+        metrics: DataIngestionMetrics,
     ) -> (
         Self,
         mpsc::Receiver<Arc<CheckpointData>>,
@@ -343,6 +400,7 @@ impl CheckpointReader {
             exit_receiver,
             data_limiter: DataLimiter::new(options.data_limit),
             options,
+            metrics,
         };
         (reader, checkpoint_recv, processed_sender, exit_sender)
     }
@@ -393,6 +451,13 @@ impl CheckpointReader {
     }
 }
 
+fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<object_store::Error>(),
+        Some(object_store::Error::NotFound { .. })
+    ) || err.to_string().contains("404")
+}
+
 pub struct DataLimiter {
     limit: usize,
     queue: BTreeMap<CheckpointSequenceNumber, usize>,
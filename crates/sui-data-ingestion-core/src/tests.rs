@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::progress_store::ExecutorProgress;
+use crate::reader::CheckpointReader;
 use crate::{DataIngestionMetrics, FileProgressStore, IndexerExecutor, WorkerPool};
 use crate::{ReaderOptions, Worker};
 use anyhow::Result;
@@ -10,6 +11,8 @@ use prometheus::Registry;
 use rand::prelude::StdRng;
 use rand::SeedableRng;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use sui_protocol_config::ProtocolConfig;
 use sui_storage::blob::{Blob, BlobEncoding};
@@ -106,6 +109,92 @@ async fn basic_flow() {
     assert_eq!(result.unwrap().get("test"), Some(&20));
 }
 
+/// Worker that stalls on every checkpoint, recording how many it processes at once.
+#[derive(Clone, Default)]
+struct SlowWorker {
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Worker for SlowWorker {
+    type Result = ();
+    async fn process_checkpoint(&self, _checkpoint: &CheckpointData) -> Result<()> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn concurrency_bounds_in_flight_checkpoints() {
+    let mut bundle = create_executor_bundle();
+    let worker = SlowWorker::default();
+    add_worker_pool(&mut bundle.executor, worker.clone(), 4)
+        .await
+        .unwrap();
+    let path = temp_dir();
+    for checkpoint_number in 0..20 {
+        let bytes = mock_checkpoint_data_bytes(checkpoint_number);
+        std::fs::write(path.join(format!("{}.chk", checkpoint_number)), bytes).unwrap();
+    }
+    let result = run(bundle.executor, Some(path), Some(Duration::from_secs(1))).await;
+    assert_eq!(result.unwrap().get("test"), Some(&20));
+    assert_eq!(worker.max_in_flight.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn remote_fetch_retries() {
+    let metrics = DataIngestionMetrics::new(&Registry::new());
+    let options = ReaderOptions {
+        retry_base_delay_ms: 1,
+        retry_max_delay_ms: 10,
+        retry_max_attempts: Some(3),
+        ..Default::default()
+    };
+    let fetch = |attempts: &AtomicUsize, outcomes: &[&'static str]| {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+        match outcomes.get(attempt) {
+            Some(error) => Err(anyhow::anyhow!(*error)),
+            None => Ok(attempt),
+        }
+    };
+
+    // Two transient failures, then success.
+    let attempts = &AtomicUsize::new(0);
+    let result = CheckpointReader::retry_remote_fetch(&options, &metrics, move || async move {
+        fetch(
+            attempts,
+            &["503 Service Unavailable", "operation timed out"],
+        )
+    })
+    .await;
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(metrics.data_ingestion_remote_fetch_retries.get(), 2);
+
+    // Checkpoints that are not uploaded yet are polled without counting as failures.
+    let attempts = &AtomicUsize::new(0);
+    let result = CheckpointReader::retry_remote_fetch(&options, &metrics, move || async move {
+        fetch(attempts, &["404 Not Found"; 5])
+    })
+    .await;
+    assert_eq!(result.unwrap(), 5);
+    assert_eq!(metrics.data_ingestion_remote_fetch_retries.get(), 2);
+
+    // Persistent failures give up after the configured number of attempts.
+    let attempts = &AtomicUsize::new(0);
+    let result = CheckpointReader::retry_remote_fetch(&options, &metrics, move || async move {
+        fetch(attempts, &["503 Service Unavailable"; 5])
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(metrics.data_ingestion_remote_fetch_retries.get(), 4);
+}
+
 fn temp_dir() -> std::path::PathBuf {
     tempfile::tempdir()
         .expect("Failed to open temporary directory")
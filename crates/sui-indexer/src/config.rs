@@ -2,10 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::db::ConnectionPoolConfig;
+use crate::errors::IndexerError;
 use crate::{backfill::BackfillTaskKind, handlers::pruner::PrunableTable};
 use clap::{Args, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, net::SocketAddr, path::PathBuf};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::num::NonZeroUsize;
+use std::{collections::HashMap, path::PathBuf};
 use strum::IntoEnumIterator;
 use sui_json_rpc::name_service::NameServiceConfig;
 use sui_types::base_types::{ObjectID, SuiAddress};
@@ -27,7 +30,7 @@ pub struct IndexerConfig {
     #[clap(flatten)]
     pub connection_pool_config: ConnectionPoolConfig,
 
-    #[clap(long, default_value = "0.0.0.0:9184")]
+    #[clap(long, default_value = "0.0.0.0:9184", value_parser = parse_metrics_address)]
     pub metrics_address: SocketAddr,
 
     #[command(subcommand)]
@@ -140,6 +143,18 @@ pub struct IngestionConfig {
     )]
     pub checkpoint_download_queue_size_bytes: usize,
 
+    /// Maximum number of checkpoints indexed concurrently. This bounds the indexing work only: the
+    /// committer still buffers out-of-order results without limit. Defaults to the checkpoint
+    /// download queue size.
+    #[arg(long, env = "MAX_IN_FLIGHT_CHECKPOINTS")]
+    pub max_in_flight_checkpoints: Option<NonZeroUsize>,
+
+    /// Whether to check that each transaction's balance changes net out per coin type, once gas
+    /// is accounted for, and warn about those that do not. Mints, burns, staking and coins that
+    /// become shared or frozen legitimately fail the check, so expect some noise.
+    #[arg(long, env = "CHECK_BALANCE_RECONCILIATION")]
+    pub check_balance_reconciliation: bool,
+
     /// Whether to delete processed checkpoint files from the local directory,
     /// when running Fullnode-colocated indexer.
     #[arg(long, default_value_t = true)]
@@ -150,6 +165,12 @@ impl IngestionConfig {
     const DEFAULT_CHECKPOINT_DOWNLOAD_QUEUE_SIZE: usize = 200;
     const DEFAULT_CHECKPOINT_DOWNLOAD_QUEUE_SIZE_BYTES: usize = 20_000_000;
     const DEFAULT_CHECKPOINT_DOWNLOAD_TIMEOUT: u64 = 20;
+
+    /// Number of checkpoints the primary pipeline indexes concurrently.
+    pub fn checkpoint_processing_concurrency(&self) -> usize {
+        self.max_in_flight_checkpoints
+            .map_or(self.checkpoint_download_queue_size, NonZeroUsize::get)
+    }
 }
 
 impl Default for IngestionConfig {
@@ -162,6 +183,8 @@ impl Default for IngestionConfig {
             checkpoint_download_timeout: Self::DEFAULT_CHECKPOINT_DOWNLOAD_TIMEOUT,
             checkpoint_download_queue_size_bytes:
                 Self::DEFAULT_CHECKPOINT_DOWNLOAD_QUEUE_SIZE_BYTES,
+            max_in_flight_checkpoints: None,
+            check_balance_reconciliation: false,
             gc_checkpoint_files: true,
         }
     }
@@ -441,6 +464,22 @@ pub struct BenchmarkConfig {
     pub workload_dir: Option<PathBuf>,
 }
 
+/// Parses `--metrics-address`, accepting a hostname as well as an IP, e.g. `localhost:9184`. A
+/// hostname resolves to the first address it maps to.
+fn parse_metrics_address(address: &str) -> Result<SocketAddr, IndexerError> {
+    address
+        .to_socket_addrs()
+        .map_err(|e| {
+            IndexerError::InvalidArgumentError(format!("Invalid metrics address {address}: {e}"))
+        })?
+        .next()
+        .ok_or_else(|| {
+            IndexerError::InvalidArgumentError(format!(
+                "Metrics address {address} did not resolve to any socket address"
+            ))
+        })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -495,6 +534,42 @@ mod test {
         parse_args::<IngestionSources>([]).unwrap_err();
     }
 
+    #[test]
+    fn max_in_flight_checkpoints() {
+        let config = parse_args::<IngestionConfig>(["--data-ingestion-path=/tmp/foo"]).unwrap();
+        assert_eq!(
+            config.checkpoint_processing_concurrency(),
+            config.checkpoint_download_queue_size
+        );
+
+        let config = parse_args::<IngestionConfig>([
+            "--data-ingestion-path=/tmp/foo",
+            "--max-in-flight-checkpoints=4",
+        ])
+        .unwrap();
+        assert_eq!(config.checkpoint_processing_concurrency(), 4);
+
+        // A pool without workers would never make progress.
+        parse_args::<IngestionConfig>([
+            "--data-ingestion-path=/tmp/foo",
+            "--max-in-flight-checkpoints=0",
+        ])
+        .unwrap_err();
+    }
+
+    #[test]
+    fn check_balance_reconciliation() {
+        let config = parse_args::<IngestionConfig>(["--data-ingestion-path=/tmp/foo"]).unwrap();
+        assert!(!config.check_balance_reconciliation);
+
+        let config = parse_args::<IngestionConfig>([
+            "--data-ingestion-path=/tmp/foo",
+            "--check-balance-reconciliation",
+        ])
+        .unwrap();
+        assert!(config.check_balance_reconciliation);
+    }
+
     #[test]
     fn json_rpc_config() {
         parse_args::<JsonRpcConfig>(["--rpc-client-url=http://example.com"]).unwrap();
@@ -635,4 +710,23 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn metrics_address() {
+        assert_eq!(
+            parse_metrics_address("127.0.0.1:9184").unwrap(),
+            SocketAddr::from(([127, 0, 0, 1], 9184)),
+        );
+
+        let resolved = parse_metrics_address("localhost:9184").unwrap();
+        assert!(resolved.ip().is_loopback());
+        assert_eq!(resolved.port(), 9184);
+
+        for invalid in ["0.0.0.0", "localhost:port", ""] {
+            assert!(matches!(
+                parse_metrics_address(invalid),
+                Err(IndexerError::InvalidArgumentError(_))
+            ));
+        }
+    }
 }
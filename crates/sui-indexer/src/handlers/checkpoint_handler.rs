@@ -52,6 +52,7 @@ pub async fn new_handlers(
     start_checkpoint_opt: Option<CheckpointSequenceNumber>,
     end_checkpoint_opt: Option<CheckpointSequenceNumber>,
     mvr_mode: bool,
+    check_balance_reconciliation: bool,
 ) -> Result<(CheckpointHandler, u64), IndexerError> {
     let start_checkpoint = match start_checkpoint_opt {
         Some(start_checkpoint) => start_checkpoint,
@@ -87,7 +88,12 @@ pub async fn new_handlers(
         mvr_mode
     ));
     Ok((
-        CheckpointHandler::new(state, metrics, indexed_checkpoint_sender),
+        CheckpointHandler::new(
+            state,
+            metrics,
+            indexed_checkpoint_sender,
+            check_balance_reconciliation,
+        ),
         start_checkpoint,
     ))
 }
@@ -96,6 +102,7 @@ pub struct CheckpointHandler {
     state: PgIndexerStore,
     metrics: IndexerMetrics,
     indexed_checkpoint_sender: mysten_metrics::metered_channel::Sender<CheckpointDataToCommit>,
+    check_balance_reconciliation: bool,
 }
 
 #[async_trait]
@@ -126,6 +133,7 @@ impl Worker for CheckpointHandler {
             checkpoint,
             Arc::new(self.metrics.clone()),
             Self::index_packages(std::slice::from_ref(checkpoint), &self.metrics),
+            self.check_balance_reconciliation,
         )
         .await?;
         self.indexed_checkpoint_sender.send(checkpoint_data).await?;
@@ -138,11 +146,13 @@ impl CheckpointHandler {
         state: PgIndexerStore,
         metrics: IndexerMetrics,
         indexed_checkpoint_sender: mysten_metrics::metered_channel::Sender<CheckpointDataToCommit>,
+        check_balance_reconciliation: bool,
     ) -> Self {
         Self {
             state,
             metrics,
             indexed_checkpoint_sender,
+            check_balance_reconciliation,
         }
     }
 
@@ -264,6 +274,7 @@ impl CheckpointHandler {
         data: &CheckpointData,
         metrics: Arc<IndexerMetrics>,
         packages: Vec<IndexedPackage>,
+        check_balance_reconciliation: bool,
     ) -> Result<CheckpointDataToCommit, IndexerError> {
         let checkpoint_seq = data.checkpoint_summary.sequence_number;
         info!(checkpoint_seq, "Indexing checkpoint data blob");
@@ -291,6 +302,7 @@ impl CheckpointHandler {
                     checkpoint_summary,
                     checkpoint_contents,
                     &metrics,
+                    check_balance_reconciliation,
                 )
                 .await?;
 
@@ -343,6 +355,7 @@ impl CheckpointHandler {
         checkpoint_summary: &CertifiedCheckpointSummary,
         checkpoint_contents: &CheckpointContents,
         metrics: &IndexerMetrics,
+        check_balance_reconciliation: bool,
     ) -> IndexerResult<(
         Vec<IndexedTransaction>,
         Vec<IndexedEvent>,
@@ -427,7 +440,7 @@ impl CheckpointHandler {
             let objects: Vec<_> = input_objects.iter().chain(output_objects.iter()).collect();
 
             let (balance_change, object_changes) =
-                TxChangesProcessor::new(&objects, metrics.clone())
+                TxChangesProcessor::new(&objects, metrics.clone(), check_balance_reconciliation)
                     .get_changes(tx, fx, &tx_digest)
                     .await?;
 
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use async_trait::async_trait;
 use sui_json_rpc::get_balance_changes_from_effect;
 use sui_json_rpc::get_object_changes;
+use sui_json_rpc::unreconciled_balance_changes;
 use sui_json_rpc::ObjectProvider;
 use sui_rest_api::CheckpointData;
 use sui_types::base_types::ObjectID;
@@ -14,6 +15,7 @@ use sui_types::digests::TransactionDigest;
 use sui_types::effects::{TransactionEffects, TransactionEffectsAPI};
 use sui_types::object::Object;
 use sui_types::transaction::{TransactionData, TransactionDataAPI};
+use tracing::warn;
 
 use crate::errors::IndexerError;
 use crate::metrics::IndexerMetrics;
@@ -58,10 +60,16 @@ impl Default for InMemObjectCache {
 pub struct TxChangesProcessor {
     object_cache: InMemObjectCache,
     metrics: IndexerMetrics,
+    /// See `IngestionConfig::check_balance_reconciliation`.
+    check_balance_reconciliation: bool,
 }
 
 impl TxChangesProcessor {
-    pub fn new(objects: &[&Object], metrics: IndexerMetrics) -> Self {
+    pub fn new(
+        objects: &[&Object],
+        metrics: IndexerMetrics,
+        check_balance_reconciliation: bool,
+    ) -> Self {
         let mut object_cache = InMemObjectCache::new();
         for obj in objects {
             object_cache.insert_object(<&Object>::clone(obj).clone());
@@ -69,6 +77,7 @@ impl TxChangesProcessor {
         Self {
             object_cache,
             metrics,
+            check_balance_reconciliation,
         }
     }
 
@@ -109,6 +118,19 @@ impl TxChangesProcessor {
             None,
         )
         .await?;
+        if self.check_balance_reconciliation {
+            // Mints, burns and staking legitimately trip this, so it is only reported, not failed
+            // on.
+            let unreconciled = unreconciled_balance_changes(effects, &balance_change);
+            if !unreconciled.is_empty() {
+                self.metrics.indexing_unreconciled_balance_changes.inc();
+                warn!(
+                    ?tx_digest,
+                    ?unreconciled,
+                    "Balance changes do not reconcile"
+                );
+            }
+        }
         Ok((balance_change, object_change))
     }
 }
@@ -100,6 +100,7 @@ impl Indexer {
             config.start_checkpoint,
             config.end_checkpoint,
             mvr_mode,
+            config.check_balance_reconciliation,
         )
         .await?;
         // Ingestion task watermarks are snapshotted once on indexer startup based on the
@@ -117,7 +118,7 @@ impl Indexer {
         let worker_pool = WorkerPool::new(
             worker,
             "primary".to_string(),
-            config.checkpoint_download_queue_size,
+            config.checkpoint_processing_concurrency(),
         );
         executor.register(worker_pool).await?;
         let (exit_sender, exit_receiver) = oneshot::channel();
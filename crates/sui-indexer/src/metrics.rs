@@ -96,6 +96,10 @@ pub struct IndexerMetrics {
     pub checkpoint_index_latency: Histogram,
     pub indexing_batch_size: IntGauge,
     pub indexing_tx_object_changes_latency: Histogram,
+    /// Transactions whose balance changes, net of gas, do not sum to zero per coin type. Only
+    /// counted with `--check-balance-reconciliation`. Mints, burns, staking and coins that become
+    /// shared or frozen all count, so a non-zero value alone does not indicate a bug.
+    pub indexing_unreconciled_balance_changes: IntCounter,
     pub indexing_objects_latency: Histogram,
     pub indexing_get_object_in_mem_hit: IntCounter,
     pub indexing_get_object_db_hit: IntCounter,
@@ -342,6 +346,12 @@ impl IndexerMetrics {
                 registry,
             )
             .unwrap(),
+            indexing_unreconciled_balance_changes: register_int_counter_with_registry!(
+                "indexing_unreconciled_balance_changes",
+                "Total number of transactions whose balance changes do not net out per coin type, when the check is enabled",
+                registry,
+            )
+            .unwrap(),
             indexing_objects_latency: register_histogram_with_registry!(
                 "indexing_objects_latency",
                 "Time spent in indexing objects",
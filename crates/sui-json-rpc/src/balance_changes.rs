@@ -3,52 +3,177 @@
 
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::Neg;
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures::{stream, StreamExt, TryStreamExt};
 use move_core_types::language_storage::TypeTag;
-use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{RwLock, Semaphore};
 
 use sui_json_rpc_types::BalanceChange;
-use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber};
+use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress};
 use sui_types::coin::Coin;
 use sui_types::digests::ObjectDigest;
 use sui_types::effects::{TransactionEffects, TransactionEffectsAPI};
+use sui_types::error::{SuiError, UserInputError};
 use sui_types::execution_status::ExecutionStatus;
+use sui_types::full_checkpoint_content::{CheckpointData, CheckpointTransaction};
 use sui_types::gas_coin::GAS;
 use sui_types::object::{Object, Owner};
 use sui_types::storage::WriteKind;
 use sui_types::transaction::InputObjectKind;
 use tracing::instrument;
 
+/// Default for [`ObjectProvider::max_concurrent_fetches`].
+pub const DEFAULT_MAX_CONCURRENT_COIN_FETCHES: usize = 32;
+
 #[instrument(skip_all, fields(transaction_digest = %effects.transaction_digest()))]
-pub async fn get_balance_changes_from_effect<P: ObjectProvider<Error = E>, E>(
+pub async fn get_balance_changes_from_effect<P: ObjectProvider<Error = E>, E: From<SuiError>>(
     object_provider: &P,
     effects: &TransactionEffects,
     input_objs: Vec<InputObjectKind>,
     mocked_coin: Option<ObjectID>,
 ) -> Result<Vec<BalanceChange>, E> {
-    let (_, gas_owner) = effects.gas_object();
-
     // Only charge gas when tx fails, skip all object parsing
     if effects.status() != &ExecutionStatus::Success {
-        return Ok(vec![BalanceChange {
-            owner: gas_owner,
-            coin_type: GAS::type_tag(),
-            amount: effects.gas_cost_summary().net_gas_usage().neg() as i128,
-        }]);
+        return Ok(vec![gas_only_balance_change(effects)]);
     }
 
-    let all_mutated = effects
+    get_balance_changes(
+        object_provider,
+        &modified_objects(effects, &input_objs, mocked_coin),
+        &changed_objects(effects, mocked_coin),
+    )
+    .await
+}
+
+/// Like `get_balance_changes_from_effect`, but only computes the net change per coin type for
+/// `owner`. Returns `None` if none of `owner`'s balances changed. Coins `owner` holds as a
+/// single-owner consensus object are folded together with its address-owned coins, and every
+/// change is reported as `Owner::AddressOwner(owner)`. The effects record the owner of every
+/// output object, so coins that end up with other owners are never fetched.
+#[instrument(skip_all, fields(transaction_digest = %effects.transaction_digest()))]
+pub async fn get_owner_balance_changes_from_effect<
+    P: ObjectProvider<Error = E>,
+    E: From<SuiError>,
+>(
+    object_provider: &P,
+    effects: &TransactionEffects,
+    input_objs: Vec<InputObjectKind>,
+    mocked_coin: Option<ObjectID>,
+    owner: SuiAddress,
+) -> Result<Option<Vec<BalanceChange>>, E> {
+    let owned_by = |o: &Owner| owning_address(o) == Some(owner);
+
+    if effects.status() != &ExecutionStatus::Success {
+        let gas_change = gas_only_balance_change(effects);
+        return Ok(owned_by(&gas_change.owner).then(|| {
+            vec![BalanceChange {
+                owner: Owner::AddressOwner(owner),
+                ..gas_change
+            }]
+        }));
+    }
+
+    let owner_mutated = effects
         .all_changed_objects()
         .into_iter()
-        .filter_map(|((id, version, digest), _, _)| {
-            if matches!(mocked_coin, Some(coin) if id == coin) {
+        .filter_map(|((id, version, digest), new_owner, _)| {
+            if !owned_by(&new_owner) || is_mocked_coin(&id, mocked_coin) {
                 return None;
             }
             Some((id, version, Some(digest)))
         })
         .collect::<Vec<_>>();
 
+    let mut nets = BTreeMap::<TypeTag, i128>::new();
+    for change in get_balance_changes(
+        object_provider,
+        &modified_objects(effects, &input_objs, mocked_coin),
+        &owner_mutated,
+    )
+    .await?
+    {
+        if owned_by(&change.owner) {
+            *nets.entry(change.coin_type).or_default() += change.amount;
+        }
+    }
+    let changes: Vec<_> = nets
+        .into_iter()
+        .filter(|(_, amount)| *amount != 0)
+        .map(|(coin_type, amount)| BalanceChange {
+            owner: Owner::AddressOwner(owner),
+            coin_type,
+            amount,
+        })
+        .collect();
+
+    Ok((!changes.is_empty()).then_some(changes))
+}
+
+fn gas_only_balance_change(effects: &TransactionEffects) -> BalanceChange {
+    let (_, gas_owner) = effects.gas_object();
+    BalanceChange {
+        owner: gas_owner,
+        coin_type: GAS::type_tag(),
+        amount: effects.gas_cost_summary().net_gas_usage().neg() as i128,
+    }
+}
+
+/// `mocked_coin` is the gas coin a dry run fabricates when the transaction supplies no gas
+/// payment. It never existed on chain, so it is left out of both the inputs and the outputs of
+/// every balance computation: its input version cannot be fetched and its output balance is not
+/// real. This is the only place that interprets `mocked_coin`, so all paths agree on it. Gas
+/// charged to it by a failed transaction is still reported, since that change comes from the gas
+/// cost summary rather than from the coin.
+fn is_mocked_coin(id: &ObjectID, mocked_coin: Option<ObjectID>) -> bool {
+    mocked_coin.as_ref() == Some(id)
+}
+
+/// The address whose balance coins held by `owner` count towards, if any. Consensus objects are
+/// only ever singly owned today, so they belong to their authenticator's address.
+fn owning_address(owner: &Owner) -> Option<SuiAddress> {
+    match owner {
+        Owner::AddressOwner(address) => Some(*address),
+        Owner::ConsensusV2 { authenticator, .. } => Some(*authenticator.as_single_owner()),
+        Owner::ObjectOwner(_) | Owner::Shared { .. } | Owner::Immutable => None,
+    }
+}
+
+/// Whether coins held by `owner` count towards balance changes. Shared and immutable coins belong
+/// to no one, so they are left out, and need not be fetched at all.
+fn holds_balance(owner: &Owner) -> bool {
+    !matches!(owner, Owner::Shared { .. } | Owner::Immutable)
+}
+
+/// The output version and digest of every object written by the transaction, except objects that
+/// cannot hold a balance.
+fn changed_objects(
+    effects: &TransactionEffects,
+    mocked_coin: Option<ObjectID>,
+) -> Vec<(ObjectID, SequenceNumber, Option<ObjectDigest>)> {
+    effects
+        .all_changed_objects()
+        .into_iter()
+        .filter_map(|((id, version, digest), owner, _)| {
+            if is_mocked_coin(&id, mocked_coin) || !holds_balance(&owner) {
+                return None;
+            }
+            Some((id, version, Some(digest)))
+        })
+        .collect()
+}
+
+/// The input version of every object modified by the transaction, with its digest when it is an
+/// owned input. Inputs that cannot hold a balance are skipped, judged by the input owner the
+/// effects record. Effects v1 do not record it, but predate consensus-address-owned objects, so
+/// there shared inputs are skipped by their input kind.
+fn modified_objects(
+    effects: &TransactionEffects,
+    input_objs: &[InputObjectKind],
+    mocked_coin: Option<ObjectID>,
+) -> Vec<(ObjectID, SequenceNumber, Option<ObjectDigest>)> {
     let input_objs_to_digest = input_objs
         .iter()
         .filter_map(|k| match k {
@@ -56,57 +181,61 @@ pub async fn get_balance_changes_from_effect<P: ObjectProvider<Error = E>, E>(
             InputObjectKind::MovePackage(_) | InputObjectKind::SharedMoveObject { .. } => None,
         })
         .collect::<HashMap<ObjectID, ObjectDigest>>();
+    // Consensus-address-owned objects are passed as shared inputs too, so the input kind alone
+    // cannot tell whether an input holds a balance.
+    let no_balance_inputs = match effects {
+        TransactionEffects::V1(_) => input_objs
+            .iter()
+            .filter_map(|k| match k {
+                InputObjectKind::SharedMoveObject { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect::<HashSet<_>>(),
+        TransactionEffects::V2(_) => effects
+            .old_object_metadata()
+            .into_iter()
+            .filter_map(|((id, _, _), owner)| (!holds_balance(&owner)).then_some(id))
+            .collect(),
+    };
     let unwrapped_then_deleted = effects
         .unwrapped_then_deleted()
         .iter()
         .map(|e| e.0)
         .collect::<HashSet<_>>();
-    get_balance_changes(
-        object_provider,
-        &effects
-            .modified_at_versions()
-            .into_iter()
-            .filter_map(|(id, version)| {
-                if matches!(mocked_coin, Some(coin) if id == coin) {
-                    return None;
-                }
-                // We won't be able to get dynamic object from object provider today
-                if unwrapped_then_deleted.contains(&id) {
-                    return None;
-                }
-                Some((id, version, input_objs_to_digest.get(&id).cloned()))
-            })
-            .collect::<Vec<_>>(),
-        &all_mutated,
-    )
-    .await
+    effects
+        .modified_at_versions()
+        .into_iter()
+        .filter_map(|(id, version)| {
+            if is_mocked_coin(&id, mocked_coin) {
+                return None;
+            }
+            // We won't be able to get dynamic object from object provider today
+            if unwrapped_then_deleted.contains(&id) || no_balance_inputs.contains(&id) {
+                return None;
+            }
+            Some((id, version, input_objs_to_digest.get(&id).cloned()))
+        })
+        .collect()
 }
 
 #[instrument(skip_all)]
-pub async fn get_balance_changes<P: ObjectProvider<Error = E>, E>(
+pub async fn get_balance_changes<P: ObjectProvider<Error = E>, E: From<SuiError>>(
     object_provider: &P,
     modified_at_version: &[(ObjectID, SequenceNumber, Option<ObjectDigest>)],
     all_mutated: &[(ObjectID, SequenceNumber, Option<ObjectDigest>)],
 ) -> Result<Vec<BalanceChange>, E> {
     // 1. subtract all input coins
-    let balances = fetch_coins(object_provider, modified_at_version)
-        .await?
-        .into_iter()
-        .fold(
-            BTreeMap::<_, i128>::new(),
-            |mut acc, (owner, type_, amount)| {
-                *acc.entry((owner, type_)).or_default() -= amount as i128;
-                acc
-            },
-        );
+    let balances = accumulate_balances(
+        BTreeMap::new(),
+        fetch_coins(object_provider, modified_at_version).await?,
+        i128::checked_sub,
+    )?;
     // 2. add all mutated coins
-    let balances = fetch_coins(object_provider, all_mutated)
-        .await?
-        .into_iter()
-        .fold(balances, |mut acc, (owner, type_, amount)| {
-            *acc.entry((owner, type_)).or_default() += amount as i128;
-            acc
-        });
+    let balances = accumulate_balances(
+        balances,
+        fetch_coins(object_provider, all_mutated).await?,
+        i128::checked_add,
+    )?;
 
     Ok(balances
         .into_iter()
@@ -123,15 +252,199 @@ pub async fn get_balance_changes<P: ObjectProvider<Error = E>, E>(
         .collect())
 }
 
+/// Folds `coins` into the per-owner, per-coin-type `balances` with `op`, failing instead of
+/// wrapping if a running total leaves the `i128` range.
+fn accumulate_balances(
+    balances: BTreeMap<(Owner, TypeTag), i128>,
+    coins: Vec<(ObjectID, Owner, TypeTag, u64)>,
+    op: fn(i128, i128) -> Option<i128>,
+) -> Result<BTreeMap<(Owner, TypeTag), i128>, SuiError> {
+    coins
+        .into_iter()
+        .try_fold(balances, |mut acc, (_, owner, type_, amount)| {
+            let key = (owner, type_);
+            let balance = acc.get(&key).copied().unwrap_or_default();
+            let balance =
+                op(balance, amount as i128).ok_or_else(|| SuiError::GenericAuthorityError {
+                    error: format!("Balance change overflow for coin type {}", key.1),
+                })?;
+            acc.insert(key, balance);
+            Ok(acc)
+        })
+}
+
+/// Coin types whose balance changes do not reconcile across owners, with the amount they are off
+/// by. A transaction only moves coins between owners, except for the SUI it pays in gas, so every
+/// coin type should net to zero and SUI to the negated net gas usage. A non-empty result means a
+/// mint or burn, SUI moving in or out of coins through staking, or a bug in the computation, so
+/// callers should report it rather than fail on it.
+pub fn unreconciled_balance_changes(
+    effects: &TransactionEffects,
+    changes: &[BalanceChange],
+) -> Vec<(TypeTag, i128)> {
+    let mut totals = BTreeMap::from([(
+        GAS::type_tag(),
+        effects.gas_cost_summary().net_gas_usage() as i128,
+    )]);
+    for change in changes {
+        *totals.entry(change.coin_type.clone()).or_default() += change.amount;
+    }
+    totals
+        .into_iter()
+        .filter(|(_, total)| *total != 0)
+        .collect()
+}
+
+/// The state of a single coin object before and after a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoinObjectChange {
+    pub object_id: ObjectID,
+    pub coin_type: TypeTag,
+    /// Owner and balance before the transaction, `None` if the coin was created by it.
+    pub input: Option<(Owner, u64)>,
+    /// Owner and balance after the transaction, `None` if the coin was deleted by it.
+    pub output: Option<(Owner, u64)>,
+}
+
+impl CoinObjectChange {
+    /// Net change of this coin object's balance.
+    pub fn amount(&self) -> i128 {
+        let balance = |state: &Option<(Owner, u64)>| state.as_ref().map_or(0, |(_, b)| *b as i128);
+        balance(&self.output) - balance(&self.input)
+    }
+}
+
+/// Per-coin-object counterpart of `get_balance_changes_from_effect`. Every coin object read or
+/// written by the transaction is reported individually, without folding by owner, so a split or a
+/// transfer shows up even when it nets to zero for the owners involved.
+#[instrument(skip_all, fields(transaction_digest = %effects.transaction_digest()))]
+pub async fn get_coin_object_changes_from_effect<P: ObjectProvider<Error = E>, E>(
+    object_provider: &P,
+    effects: &TransactionEffects,
+    input_objs: Vec<InputObjectKind>,
+    mocked_coin: Option<ObjectID>,
+) -> Result<Vec<CoinObjectChange>, E> {
+    get_coin_object_changes(
+        object_provider,
+        &modified_objects(effects, &input_objs, mocked_coin),
+        &changed_objects(effects, mocked_coin),
+    )
+    .await
+}
+
+#[instrument(skip_all)]
+pub async fn get_coin_object_changes<P: ObjectProvider<Error = E>, E>(
+    object_provider: &P,
+    modified_at_version: &[(ObjectID, SequenceNumber, Option<ObjectDigest>)],
+    all_mutated: &[(ObjectID, SequenceNumber, Option<ObjectDigest>)],
+) -> Result<Vec<CoinObjectChange>, E> {
+    let mut changes = BTreeMap::new();
+    for (object_id, owner, coin_type, balance) in
+        fetch_coins(object_provider, modified_at_version).await?
+    {
+        changes.insert(
+            object_id,
+            CoinObjectChange {
+                object_id,
+                coin_type,
+                input: Some((owner, balance)),
+                output: None,
+            },
+        );
+    }
+    for (object_id, owner, coin_type, balance) in fetch_coins(object_provider, all_mutated).await? {
+        changes
+            .entry(object_id)
+            .or_insert_with(|| CoinObjectChange {
+                object_id,
+                coin_type,
+                input: None,
+                output: None,
+            })
+            .output = Some((owner, balance));
+    }
+    Ok(changes.into_values().collect())
+}
+
+/// A split or merge of coins of one type, recognized from a transaction's coin object changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CoinOperation {
+    /// `source` shrank and the `created` coins were split off it.
+    Split {
+        coin_type: TypeTag,
+        source: ObjectID,
+        created: Vec<ObjectID>,
+    },
+    /// The `merged` coins were deleted and `target` grew.
+    Merge {
+        coin_type: TypeTag,
+        target: ObjectID,
+        merged: Vec<ObjectID>,
+    },
+}
+
+/// Groups the changes of `get_coin_object_changes` by coin type and recognizes splits and merges.
+/// This is a heuristic on the shape of the changes, not on the commands that were executed: a
+/// split is exactly one surviving coin that shrank alongside only newly created coins, and a merge
+/// is exactly one surviving coin that grew alongside only deleted coins. Any other combination,
+/// such as a split followed by a merge of the same type, is not reported.
+pub fn detect_coin_operations(changes: &[CoinObjectChange]) -> Vec<CoinOperation> {
+    let mut by_type = BTreeMap::<_, Vec<_>>::new();
+    for change in changes {
+        by_type.entry(&change.coin_type).or_default().push(change);
+    }
+
+    let mut operations = vec![];
+    for (coin_type, changes) in by_type {
+        let (survivors, others): (Vec<_>, Vec<_>) = changes
+            .into_iter()
+            .partition(|c| c.input.is_some() && c.output.is_some());
+        let [survivor] = survivors[..] else {
+            continue;
+        };
+        let ids = others.iter().map(|c| c.object_id).collect::<Vec<_>>();
+        if ids.is_empty() {
+            continue;
+        }
+        if survivor.amount() < 0 && others.iter().all(|c| c.input.is_none()) {
+            operations.push(CoinOperation::Split {
+                coin_type: coin_type.clone(),
+                source: survivor.object_id,
+                created: ids,
+            });
+        } else if survivor.amount() > 0 && others.iter().all(|c| c.output.is_none()) {
+            operations.push(CoinOperation::Merge {
+                coin_type: coin_type.clone(),
+                target: survivor.object_id,
+                merged: ids,
+            });
+        }
+    }
+    operations
+}
+
 #[instrument(skip_all)]
 async fn fetch_coins<P: ObjectProvider<Error = E>, E>(
     object_provider: &P,
     objects: &[(ObjectID, SequenceNumber, Option<ObjectDigest>)],
-) -> Result<Vec<(Owner, TypeTag, u64)>, E> {
+) -> Result<Vec<(ObjectID, Owner, TypeTag, u64)>, E> {
+    // TODO: use multi get object
+    let objects: Vec<_> = stream::iter(objects)
+        .map(|(id, version, digest_opt)| async move {
+            object_provider
+                .get_object(id, version)
+                .await
+                .map(|o| (o, digest_opt))
+        })
+        .buffered(object_provider.max_concurrent_fetches().max(1))
+        .try_collect()
+        .await?;
+
     let mut all_mutated_coins = vec![];
-    for (id, version, digest_opt) in objects {
-        // TODO: use multi get object
-        let o = object_provider.get_object(id, version).await?;
+    for (o, digest_opt) in objects {
+        if !holds_balance(&o.owner) {
+            continue;
+        }
         if let Some(type_) = o.type_() {
             if type_.is_coin() {
                 if let Some(digest) = digest_opt {
@@ -145,6 +458,7 @@ async fn fetch_coins<P: ObjectProvider<Error = E>, E>(
                 let [coin_type]: [TypeTag; 1] =
                     type_.clone().into_type_params().try_into().unwrap();
                 all_mutated_coins.push((
+                    o.id(),
                     o.owner.clone(),
                     coin_type,
                     // we know this is a coin, safe to unwrap
@@ -169,11 +483,19 @@ pub trait ObjectProvider {
         id: &ObjectID,
         version: &SequenceNumber,
     ) -> Result<Option<Object>, Self::Error>;
+
+    /// Maximum number of `get_object` requests a single balance change computation keeps in flight
+    /// against this provider. Wrap the provider in a [`BoundedObjectProvider`] to also bound
+    /// requests across concurrent computations.
+    fn max_concurrent_fetches(&self) -> usize {
+        DEFAULT_MAX_CONCURRENT_COIN_FETCHES
+    }
 }
 
 pub struct ObjectProviderCache<P> {
     object_cache: RwLock<BTreeMap<(ObjectID, SequenceNumber), Object>>,
     last_version_cache: RwLock<BTreeMap<(ObjectID, SequenceNumber), SequenceNumber>>,
+    max_concurrent_fetches: usize,
     provider: P,
 }
 
@@ -182,10 +504,16 @@ impl<P> ObjectProviderCache<P> {
         Self {
             object_cache: Default::default(),
             last_version_cache: Default::default(),
+            max_concurrent_fetches: DEFAULT_MAX_CONCURRENT_COIN_FETCHES,
             provider,
         }
     }
 
+    pub fn with_max_concurrent_fetches(mut self, max_concurrent_fetches: usize) -> Self {
+        self.max_concurrent_fetches = max_concurrent_fetches;
+        self
+    }
+
     pub fn insert_objects_into_cache(&mut self, objects: Vec<Object>) {
         let object_cache = self.object_cache.get_mut();
         let last_version_cache = self.last_version_cache.get_mut();
@@ -236,6 +564,7 @@ impl<P> ObjectProviderCache<P> {
         Self {
             object_cache: RwLock::new(object_cache),
             last_version_cache: RwLock::new(last_version_cache),
+            max_concurrent_fetches: DEFAULT_MAX_CONCURRENT_COIN_FETCHES,
             provider,
         }
     }
@@ -249,6 +578,10 @@ where
 {
     type Error = P::Error;
 
+    fn max_concurrent_fetches(&self) -> usize {
+        self.max_concurrent_fetches
+    }
+
     async fn get_object(
         &self,
         id: &ObjectID,
@@ -270,8 +603,33 @@ where
         id: &ObjectID,
         version: &SequenceNumber,
     ) -> Result<Option<Object>, Self::Error> {
-        if let Some(version) = self.last_version_cache.read().await.get(&(*id, *version)) {
-            return Ok(self.get_object(id, version).await.ok());
+        let cached_version = self
+            .last_version_cache
+            .read()
+            .await
+            .get(&(*id, *version))
+            .copied();
+        if let Some(found_version) = cached_version {
+            // A version between the cached answer and the query may have been learned since, e.g.
+            // when objects are indexed out of order. The object cache holds every known version
+            // sorted per object, so prefer the greatest one that is still `<= version`.
+            let closest = self
+                .object_cache
+                .read()
+                .await
+                .range((*id, found_version)..=(*id, *version))
+                .next_back()
+                .map(|(_, o)| o.clone());
+            let Some(o) = closest else {
+                return Ok(self.get_object(id, &found_version).await.ok());
+            };
+            if o.version() != found_version {
+                self.last_version_cache
+                    .write()
+                    .await
+                    .insert((*id, *version), o.version());
+            }
+            return Ok(Some(o));
         }
         if let Some(o) = self
             .provider
@@ -292,3 +650,1200 @@ where
         }
     }
 }
+
+/// Limits the number of in-flight requests to `provider`. The semaphore can be shared between
+/// several providers so that the total load on a common backend stays bounded, no matter how many
+/// change computations run concurrently.
+pub struct BoundedObjectProvider<P> {
+    provider: P,
+    permits: Arc<Semaphore>,
+}
+
+impl<P> BoundedObjectProvider<P> {
+    pub fn new(provider: P, permits: Arc<Semaphore>) -> Self {
+        Self { provider, permits }
+    }
+}
+
+#[async_trait]
+impl<P, E> ObjectProvider for BoundedObjectProvider<P>
+where
+    P: ObjectProvider<Error = E> + Sync + Send,
+    E: Sync + Send,
+{
+    type Error = P::Error;
+
+    fn max_concurrent_fetches(&self) -> usize {
+        self.provider.max_concurrent_fetches()
+    }
+
+    async fn get_object(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Object, Self::Error> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("Object fetch semaphore should never be closed");
+        self.provider.get_object(id, version).await
+    }
+
+    async fn find_object_lt_or_eq_version(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Option<Object>, Self::Error> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("Object fetch semaphore should never be closed");
+        self.provider
+            .find_object_lt_or_eq_version(id, version)
+            .await
+    }
+}
+
+fn object_not_found(id: &ObjectID, version: &SequenceNumber) -> SuiError {
+    UserInputError::ObjectNotFound {
+        object_id: *id,
+        version: Some(*version),
+    }
+    .into()
+}
+
+/// `get_object` for the in-memory providers below, which all keep objects sorted by id and version.
+fn get_from_map(
+    objects: &BTreeMap<(ObjectID, SequenceNumber), Object>,
+    id: &ObjectID,
+    version: &SequenceNumber,
+) -> Result<Object, SuiError> {
+    objects
+        .get(&(*id, *version))
+        .cloned()
+        .ok_or_else(|| object_not_found(id, version))
+}
+
+/// The greatest version of `id` in `objects` that is `<= version`, if any.
+fn find_lt_or_eq_in_map(
+    objects: &BTreeMap<(ObjectID, SequenceNumber), Object>,
+    id: &ObjectID,
+    version: &SequenceNumber,
+) -> Option<Object> {
+    objects
+        .range((*id, SequenceNumber::MIN)..=(*id, *version))
+        .next_back()
+        .map(|(_, o)| o.clone())
+}
+
+/// A serializable record of every object served by a [`RecordingObjectProvider`]. The fixture
+/// itself implements `ObjectProvider`, replaying the recorded answers without any backend, which
+/// makes golden tests of the change computations possible. Map keys are tuples, so persist it with
+/// BCS rather than JSON.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ObjectFixture {
+    objects: BTreeMap<(ObjectID, SequenceNumber), Object>,
+    /// Answers to `find_object_lt_or_eq_version`, keyed by the queried version.
+    last_versions: BTreeMap<(ObjectID, SequenceNumber), Option<SequenceNumber>>,
+}
+
+#[async_trait]
+impl ObjectProvider for ObjectFixture {
+    type Error = SuiError;
+
+    async fn get_object(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Object, Self::Error> {
+        get_from_map(&self.objects, id, version)
+    }
+
+    async fn find_object_lt_or_eq_version(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Option<Object>, Self::Error> {
+        match self.last_versions.get(&(*id, *version)) {
+            Some(Some(found)) => self.get_object(id, found).await.map(Some),
+            Some(None) => Ok(None),
+            // The lookup was never recorded, so the fixture cannot tell whether the object exists.
+            None => Err(object_not_found(id, version)),
+        }
+    }
+}
+
+/// Proxies every request to `provider` and records the answers into an [`ObjectFixture`].
+pub struct RecordingObjectProvider<P> {
+    provider: P,
+    fixture: RwLock<ObjectFixture>,
+}
+
+impl<P> RecordingObjectProvider<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            fixture: Default::default(),
+        }
+    }
+
+    pub fn into_fixture(self) -> ObjectFixture {
+        self.fixture.into_inner()
+    }
+}
+
+#[async_trait]
+impl<P, E> ObjectProvider for RecordingObjectProvider<P>
+where
+    P: ObjectProvider<Error = E> + Sync + Send,
+    E: Sync + Send,
+{
+    type Error = P::Error;
+
+    fn max_concurrent_fetches(&self) -> usize {
+        self.provider.max_concurrent_fetches()
+    }
+
+    async fn get_object(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Object, Self::Error> {
+        let o = self.provider.get_object(id, version).await?;
+        self.fixture
+            .write()
+            .await
+            .objects
+            .insert((*id, *version), o.clone());
+        Ok(o)
+    }
+
+    async fn find_object_lt_or_eq_version(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Option<Object>, Self::Error> {
+        let o = self
+            .provider
+            .find_object_lt_or_eq_version(id, version)
+            .await?;
+        let mut fixture = self.fixture.write().await;
+        if let Some(o) = &o {
+            fixture.objects.insert((*id, o.version()), o.clone());
+        }
+        fixture
+            .last_versions
+            .insert((*id, *version), o.as_ref().map(|o| o.version()));
+        Ok(o)
+    }
+}
+
+/// Serves the input and output objects contained in a [`CheckpointData`], so that changes can be
+/// computed from a checkpoint download alone. Objects outside the checkpoint are reported as not
+/// found rather than looked up elsewhere.
+#[derive(Clone, Debug, Default)]
+pub struct CheckpointObjectProvider {
+    objects: BTreeMap<(ObjectID, SequenceNumber), Object>,
+}
+
+impl CheckpointObjectProvider {
+    pub fn new(checkpoint: &CheckpointData) -> Self {
+        Self::from_transactions(&checkpoint.transactions)
+    }
+
+    pub fn from_transactions<'a>(
+        transactions: impl IntoIterator<Item = &'a CheckpointTransaction>,
+    ) -> Self {
+        let objects = transactions
+            .into_iter()
+            .flat_map(|tx| tx.input_objects.iter().chain(tx.output_objects.iter()))
+            .map(|o| ((o.id(), o.version()), o.clone()))
+            .collect();
+        Self { objects }
+    }
+}
+
+#[async_trait]
+impl ObjectProvider for CheckpointObjectProvider {
+    type Error = SuiError;
+
+    async fn get_object(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Object, Self::Error> {
+        get_from_map(&self.objects, id, version)
+    }
+
+    async fn find_object_lt_or_eq_version(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Option<Object>, Self::Error> {
+        // Every transaction's inputs are part of the checkpoint, so the greatest version seen at
+        // or below `version` is the one the transaction read. If there is none, the object's
+        // history lies outside the checkpoint and its absence here proves nothing.
+        find_lt_or_eq_in_map(&self.objects, id, version)
+            .map(Some)
+            .ok_or_else(|| object_not_found(id, version))
+    }
+}
+
+/// An in-memory `ObjectProvider` over a fixed set of objects, for tests that compute changes
+/// without any backend. `find_object_lt_or_eq_version` treats the map as complete: an object with
+/// no version at or below the query is reported as absent.
+#[cfg(any(feature = "test-utils", test))]
+#[derive(Clone, Debug, Default)]
+pub struct MapObjectProvider(pub BTreeMap<(ObjectID, SequenceNumber), Object>);
+
+#[cfg(any(feature = "test-utils", test))]
+impl MapObjectProvider {
+    pub fn new(objects: impl IntoIterator<Item = Object>) -> Self {
+        Self(
+            objects
+                .into_iter()
+                .map(|o| ((o.id(), o.version()), o))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(any(feature = "test-utils", test))]
+#[async_trait]
+impl ObjectProvider for MapObjectProvider {
+    type Error = SuiError;
+
+    async fn get_object(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Object, Self::Error> {
+        get_from_map(&self.0, id, version)
+    }
+
+    async fn find_object_lt_or_eq_version(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Option<Object>, Self::Error> {
+        Ok(find_lt_or_eq_in_map(&self.0, id, version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use sui_types::base_types::random_object_ref;
+    use sui_types::crypto::{get_key_pair, AccountKeyPair};
+    use sui_types::digests::TransactionDigest;
+    use sui_types::effects::EffectsObjectChange;
+    use sui_types::execution_status::ExecutionFailureStatus;
+    use sui_types::gas::GasCostSummary;
+    use sui_types::object::{Authenticator, MoveObject};
+    use sui_types::transaction::{Transaction, TransactionData};
+
+    fn gas_coin(id: ObjectID, version: u64, owner: SuiAddress, value: u64) -> Object {
+        Object::new_move(
+            MoveObject::new_gas_coin(SequenceNumber::from_u64(version), id, value),
+            Owner::AddressOwner(owner),
+            TransactionDigest::genesis_marker(),
+        )
+    }
+
+    fn consensus_coin(id: ObjectID, version: u64, owner: SuiAddress, value: u64) -> Object {
+        let mut coin = gas_coin(id, version, owner, value);
+        coin.owner = Owner::ConsensusV2 {
+            start_version: SequenceNumber::from_u64(1),
+            authenticator: Box::new(Authenticator::SingleOwner(owner)),
+        };
+        coin
+    }
+
+    /// Builds successful effects in which every object of `inputs` is modified and every object
+    /// of `outputs` is written at the lamport version. Inputs without an output are deleted and
+    /// outputs without an input are created.
+    fn effects(
+        inputs: &[&Object],
+        outputs: &[&Object],
+        gas_object: Option<ObjectID>,
+    ) -> TransactionEffects {
+        let mut changed_objects = BTreeMap::new();
+        for input in inputs {
+            let written = outputs.iter().find(|o| o.id() == input.id()).copied();
+            changed_objects.insert(
+                input.id(),
+                EffectsObjectChange::new(
+                    Some(((input.version(), input.digest()), input.owner.clone())),
+                    written,
+                    false,
+                    written.is_none(),
+                ),
+            );
+        }
+        for output in outputs {
+            changed_objects
+                .entry(output.id())
+                .or_insert_with(|| EffectsObjectChange::new(None, Some(output), true, false));
+        }
+        TransactionEffects::new_from_execution_v2(
+            ExecutionStatus::Success,
+            0,
+            GasCostSummary::default(),
+            vec![],
+            BTreeSet::new(),
+            TransactionDigest::random(),
+            SequenceNumber::lamport_increment(inputs.iter().map(|o| o.version())),
+            changed_objects,
+            gas_object,
+            None,
+            vec![],
+        )
+    }
+
+    fn owned_inputs(inputs: &[&Object]) -> Vec<InputObjectKind> {
+        inputs
+            .iter()
+            .map(|o| InputObjectKind::ImmOrOwnedMoveObject(o.compute_object_reference()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_recorded_fixture_replays_balance_changes() {
+        let sender = SuiAddress::random_for_testing_only();
+        let recipient = SuiAddress::random_for_testing_only();
+        let (coin, new_coin) = (ObjectID::random(), ObjectID::random());
+        let provider = MapObjectProvider::new([
+            gas_coin(coin, 1, sender, 100),
+            gas_coin(coin, 2, sender, 60),
+            gas_coin(new_coin, 2, recipient, 40),
+        ]);
+        let modified_at_version = [(coin, SequenceNumber::from_u64(1), None)];
+        let all_mutated = [
+            (coin, SequenceNumber::from_u64(2), None),
+            (new_coin, SequenceNumber::from_u64(2), None),
+        ];
+
+        let recording = RecordingObjectProvider::new(provider);
+        let recorded = get_balance_changes(&recording, &modified_at_version, &all_mutated)
+            .await
+            .unwrap();
+        let last_version = recording
+            .find_object_lt_or_eq_version(&coin, &SequenceNumber::from_u64(5))
+            .await
+            .unwrap()
+            .map(|o| o.version());
+
+        let bytes = bcs::to_bytes(&recording.into_fixture()).unwrap();
+        let fixture: ObjectFixture = bcs::from_bytes(&bytes).unwrap();
+        let replayed = get_balance_changes(&fixture, &modified_at_version, &all_mutated)
+            .await
+            .unwrap();
+
+        assert_eq!(recorded, replayed);
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(
+            fixture
+                .find_object_lt_or_eq_version(&coin, &SequenceNumber::from_u64(5))
+                .await
+                .unwrap()
+                .map(|o| o.version()),
+            last_version,
+        );
+        // Nothing outside the recording is served.
+        fixture
+            .get_object(&coin, &SequenceNumber::from_u64(3))
+            .await
+            .unwrap_err();
+    }
+
+    /// Tracks the number of concurrent requests reaching the wrapped provider.
+    struct CountingProvider {
+        provider: MapObjectProvider,
+        fetches: Arc<AtomicUsize>,
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ObjectProvider for CountingProvider {
+        type Error = SuiError;
+
+        async fn get_object(
+            &self,
+            id: &ObjectID,
+            version: &SequenceNumber,
+        ) -> Result<Object, Self::Error> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            self.provider.get_object(id, version).await
+        }
+
+        async fn find_object_lt_or_eq_version(
+            &self,
+            id: &ObjectID,
+            version: &SequenceNumber,
+        ) -> Result<Option<Object>, Self::Error> {
+            self.provider
+                .find_object_lt_or_eq_version(id, version)
+                .await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bounded_provider_limits_concurrent_fetches() {
+        const LIMIT: usize = 3;
+        let owner = SuiAddress::random_for_testing_only();
+        let permits = Arc::new(Semaphore::new(LIMIT));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        // Two concurrent computations, each spending 20 coins, sharing one semaphore.
+        let computations = (0..2).map(|_| {
+            let coins: Vec<_> = (0..20).map(|_| ObjectID::random()).collect();
+            let provider = BoundedObjectProvider::new(
+                CountingProvider {
+                    provider: MapObjectProvider::new(
+                        coins.iter().map(|id| gas_coin(*id, 1, owner, 10)),
+                    ),
+                    fetches: Default::default(),
+                    in_flight: in_flight.clone(),
+                    max_in_flight: max_in_flight.clone(),
+                },
+                permits.clone(),
+            );
+            async move {
+                let modified_at_version: Vec<_> = coins
+                    .iter()
+                    .map(|id| (*id, SequenceNumber::from_u64(1), None))
+                    .collect();
+                get_balance_changes(&provider, &modified_at_version, &[]).await
+            }
+        });
+
+        for changes in futures::future::join_all(computations).await {
+            let changes = changes.unwrap();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].amount, -200);
+        }
+        // Fetches overlap up to the limit, but never beyond it.
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), LIMIT);
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_fetches() {
+        const LIMIT: usize = 4;
+        let owner = SuiAddress::random_for_testing_only();
+        let coins: Vec<_> = (0..20).map(|_| ObjectID::random()).collect();
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let provider = ObjectProviderCache::new(CountingProvider {
+            provider: MapObjectProvider::new(coins.iter().map(|id| gas_coin(*id, 1, owner, 10))),
+            fetches: Default::default(),
+            in_flight: Default::default(),
+            max_in_flight: max_in_flight.clone(),
+        })
+        .with_max_concurrent_fetches(LIMIT);
+
+        let modified_at_version: Vec<_> = coins
+            .iter()
+            .map(|id| (*id, SequenceNumber::from_u64(1), None))
+            .collect();
+        let changes = get_balance_changes(&provider, &modified_at_version, &[])
+            .await
+            .unwrap();
+        assert_eq!(changes[0].amount, -200);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), LIMIT);
+    }
+
+    #[tokio::test]
+    async fn test_owner_balance_changes() {
+        let sender = SuiAddress::random_for_testing_only();
+        let (recipient, other, bystander) = (
+            SuiAddress::random_for_testing_only(),
+            SuiAddress::random_for_testing_only(),
+            SuiAddress::random_for_testing_only(),
+        );
+        let coin = ObjectID::random();
+        let sender_before = gas_coin(coin, 1, sender, 100);
+        let sender_after = gas_coin(coin, 2, sender, 50);
+        let to_recipient = gas_coin(ObjectID::random(), 2, recipient, 30);
+        let to_other = gas_coin(ObjectID::random(), 2, other, 20);
+        let effects = effects(
+            &[&sender_before],
+            &[&sender_after, &to_recipient, &to_other],
+            None,
+        );
+        let input_objs = owned_inputs(&[&sender_before]);
+        let provider = || {
+            RecordingObjectProvider::new(MapObjectProvider::new([
+                sender_before.clone(),
+                sender_after.clone(),
+                to_recipient.clone(),
+                to_other.clone(),
+            ]))
+        };
+
+        let all = get_balance_changes_from_effect(&provider(), &effects, input_objs.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+
+        for (owner, amount) in [(sender, -50), (recipient, 30), (other, 20)] {
+            let provider = provider();
+            let changes = get_owner_balance_changes_from_effect(
+                &provider,
+                &effects,
+                input_objs.clone(),
+                None,
+                owner,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            assert_eq!(
+                changes,
+                vec![BalanceChange {
+                    owner: Owner::AddressOwner(owner),
+                    coin_type: GAS::type_tag(),
+                    amount,
+                }]
+            );
+            assert!(all.contains(&changes[0]));
+
+            // Coins received by other owners are never fetched.
+            let fetched = provider.into_fixture().objects;
+            for received in [&to_recipient, &to_other] {
+                let fetched_received = fetched.contains_key(&(received.id(), received.version()));
+                assert_eq!(
+                    fetched_received,
+                    received.owner == Owner::AddressOwner(owner)
+                );
+            }
+        }
+
+        assert!(get_owner_balance_changes_from_effect(
+            &provider(),
+            &effects,
+            input_objs,
+            None,
+            bystander,
+        )
+        .await
+        .unwrap()
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_owner_balance_changes_include_consensus_coins() {
+        let sender = SuiAddress::random_for_testing_only();
+        let recipient = SuiAddress::random_for_testing_only();
+        let (coin, consensus) = (ObjectID::random(), ObjectID::random());
+        let coin_before = gas_coin(coin, 1, sender, 100);
+        let coin_after = gas_coin(coin, 2, sender, 70);
+        let consensus_before = consensus_coin(consensus, 1, sender, 50);
+        let consensus_after = consensus_coin(consensus, 2, sender, 40);
+        let to_recipient = consensus_coin(ObjectID::random(), 2, recipient, 40);
+        let effects = effects(
+            &[&coin_before, &consensus_before],
+            &[&coin_after, &consensus_after, &to_recipient],
+            None,
+        );
+        let mut input_objs = owned_inputs(&[&coin_before]);
+        input_objs.push(InputObjectKind::SharedMoveObject {
+            id: consensus,
+            initial_shared_version: SequenceNumber::from_u64(1),
+            mutable: true,
+        });
+        let provider = MapObjectProvider::new([
+            coin_before,
+            coin_after,
+            consensus_before,
+            consensus_after,
+            to_recipient,
+        ]);
+
+        // Address-owned and consensus-owned coins fold into one net change per coin type.
+        for (owner, amount) in [(sender, -40), (recipient, 40)] {
+            let changes = get_owner_balance_changes_from_effect(
+                &provider,
+                &effects,
+                input_objs.clone(),
+                None,
+                owner,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            assert_eq!(
+                changes,
+                vec![BalanceChange {
+                    owner: Owner::AddressOwner(owner),
+                    coin_type: GAS::type_tag(),
+                    amount,
+                }]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_coin_object_changes_for_split() {
+        let owner = SuiAddress::random_for_testing_only();
+        let coin = ObjectID::random();
+        let before = gas_coin(coin, 1, owner, 100);
+        let after = gas_coin(coin, 2, owner, 60);
+        let split = gas_coin(ObjectID::random(), 2, owner, 40);
+        let effects = effects(&[&before], &[&after, &split], None);
+        let provider = MapObjectProvider::new([before.clone(), after.clone(), split.clone()]);
+
+        // The split nets to zero for the owner...
+        let balance_changes =
+            get_balance_changes_from_effect(&provider, &effects, owned_inputs(&[&before]), None)
+                .await
+                .unwrap();
+        assert!(balance_changes.is_empty());
+
+        // ...but each coin object reports its own balances.
+        let coin_changes = get_coin_object_changes_from_effect(
+            &provider,
+            &effects,
+            owned_inputs(&[&before]),
+            None,
+        )
+        .await
+        .unwrap();
+        let owner = Owner::AddressOwner(owner);
+        let mut expected = vec![
+            CoinObjectChange {
+                object_id: coin,
+                coin_type: GAS::type_tag(),
+                input: Some((owner.clone(), 100)),
+                output: Some((owner.clone(), 60)),
+            },
+            CoinObjectChange {
+                object_id: split.id(),
+                coin_type: GAS::type_tag(),
+                input: None,
+                output: Some((owner, 40)),
+            },
+        ];
+        expected.sort_by_key(|c| c.object_id);
+        assert_eq!(coin_changes, expected);
+        let amounts: BTreeMap<_, _> = coin_changes
+            .iter()
+            .map(|c| (c.object_id, c.amount()))
+            .collect();
+        assert_eq!(amounts[&coin], -40);
+        assert_eq!(amounts[&split.id()], 40);
+    }
+
+    #[tokio::test]
+    async fn test_coin_merged_into_gas() {
+        let owner = SuiAddress::random_for_testing_only();
+        let recipient = SuiAddress::random_for_testing_only();
+        let (gas, coin) = (ObjectID::random(), ObjectID::random());
+        let gas_before = gas_coin(gas, 1, owner, 100);
+        let coin_before = gas_coin(coin, 1, owner, 50);
+        let inputs = [&gas_before, &coin_before];
+
+        // `coin` is merged into the gas coin, which then pays 10 for gas.
+        let gas_after = gas_coin(gas, 2, owner, 140);
+        let provider =
+            MapObjectProvider::new([gas_before.clone(), coin_before.clone(), gas_after.clone()]);
+        let changes = get_balance_changes_from_effect(
+            &provider,
+            &effects(&inputs, &[&gas_after], Some(gas)),
+            owned_inputs(&inputs),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            changes,
+            vec![BalanceChange {
+                owner: Owner::AddressOwner(owner),
+                coin_type: GAS::type_tag(),
+                amount: -10,
+            }]
+        );
+
+        // Same merge, but 30 of the merged funds are also sent to `recipient`.
+        let gas_after = gas_coin(gas, 2, owner, 110);
+        let sent = gas_coin(ObjectID::random(), 2, recipient, 30);
+        let provider =
+            MapObjectProvider::new([gas_before, coin_before, gas_after.clone(), sent.clone()]);
+        let changes = get_balance_changes_from_effect(
+            &provider,
+            &effects(&inputs, &[&gas_after, &sent], Some(gas)),
+            owned_inputs(&inputs),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(changes.len(), 2);
+        for (owner, amount) in [(owner, -40), (recipient, 30)] {
+            assert!(changes.contains(&BalanceChange {
+                owner: Owner::AddressOwner(owner),
+                coin_type: GAS::type_tag(),
+                amount,
+            }));
+        }
+    }
+
+    #[test]
+    fn test_balance_accumulation_overflow() {
+        let owner = Owner::AddressOwner(SuiAddress::random_for_testing_only());
+        let coin = (ObjectID::random(), owner.clone(), GAS::type_tag(), u64::MAX);
+
+        let near_max = BTreeMap::from([((owner.clone(), GAS::type_tag()), i128::MAX - 1)]);
+        assert!(accumulate_balances(near_max, vec![coin.clone()], i128::checked_add).is_err());
+
+        let near_min = BTreeMap::from([((owner.clone(), GAS::type_tag()), i128::MIN + 1)]);
+        assert!(accumulate_balances(near_min, vec![coin.clone()], i128::checked_sub).is_err());
+
+        // Totals that stay in range are unaffected.
+        let balances = accumulate_balances(BTreeMap::new(), vec![coin], i128::checked_sub).unwrap();
+        assert_eq!(balances[&(owner, GAS::type_tag())], -(u64::MAX as i128));
+    }
+
+    #[tokio::test]
+    async fn test_object_cache_lt_or_eq_prefers_closer_known_version() {
+        let owner = SuiAddress::random_for_testing_only();
+        let id = ObjectID::random();
+        let v1 = gas_coin(id, 1, owner, 100);
+        let v3 = gas_coin(id, 3, owner, 300);
+        let mut cache = ObjectProviderCache::new(MapObjectProvider::new([v1.clone()]));
+
+        let query = SequenceNumber::from_u64(5);
+        let found = cache
+            .find_object_lt_or_eq_version(&id, &query)
+            .await
+            .unwrap();
+        assert_eq!(found.map(|o| o.version()), Some(v1.version()));
+
+        // An intermediate version is learned after the lookup was cached.
+        cache.insert_objects_into_cache(vec![v3.clone()]);
+        let found = cache
+            .find_object_lt_or_eq_version(&id, &query)
+            .await
+            .unwrap();
+        assert_eq!(found.map(|o| o.version()), Some(v3.version()));
+
+        // Queries below the new version still resolve to the older one.
+        let found = cache
+            .find_object_lt_or_eq_version(&id, &SequenceNumber::from_u64(2))
+            .await
+            .unwrap();
+        assert_eq!(found.map(|o| o.version()), Some(v1.version()));
+    }
+
+    #[tokio::test]
+    async fn test_balance_changes_from_checkpoint_objects() {
+        let (sender, key): (_, AccountKeyPair) = get_key_pair();
+        let recipient = SuiAddress::random_for_testing_only();
+        let before = gas_coin(ObjectID::random(), 1, sender, 1000);
+        let after = gas_coin(before.id(), 2, sender, 600);
+        let received = gas_coin(ObjectID::random(), 2, recipient, 400);
+
+        let transaction = Transaction::from_data_and_signer(
+            TransactionData::new_transfer(
+                recipient,
+                random_object_ref(),
+                sender,
+                before.compute_object_reference(),
+                1_000_000,
+                1,
+            ),
+            vec![&key],
+        );
+        let checkpoint_tx = CheckpointTransaction {
+            transaction,
+            effects: effects(&[&before], &[&after, &received], Some(before.id())),
+            events: None,
+            input_objects: vec![before.clone()],
+            output_objects: vec![after, received],
+        };
+        let provider = CheckpointObjectProvider::from_transactions([&checkpoint_tx]);
+
+        let changes = get_balance_changes_from_effect(
+            &provider,
+            &checkpoint_tx.effects,
+            owned_inputs(&[&before]),
+            None,
+        )
+        .await
+        .unwrap();
+        let amounts: BTreeMap<_, _> = changes.into_iter().map(|c| (c.owner, c.amount)).collect();
+        assert_eq!(
+            amounts,
+            BTreeMap::from([
+                (Owner::AddressOwner(sender), -400),
+                (Owner::AddressOwner(recipient), 400),
+            ])
+        );
+
+        // Objects the checkpoint does not contain are errors, not absent objects.
+        assert!(provider
+            .find_object_lt_or_eq_version(&ObjectID::random(), &SequenceNumber::from_u64(1))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shared_coins_are_not_balance_changes() {
+        let id = ObjectID::random();
+        let shared = |version, value| {
+            Object::new_move(
+                MoveObject::new_gas_coin(SequenceNumber::from_u64(version), id, value),
+                Owner::Shared {
+                    initial_shared_version: SequenceNumber::from_u64(1),
+                },
+                TransactionDigest::genesis_marker(),
+            )
+        };
+        let (before, after) = (shared(1, 1000), shared(2, 400));
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let provider = CountingProvider {
+            provider: MapObjectProvider::new([before.clone(), after.clone()]),
+            fetches: fetches.clone(),
+            in_flight: Default::default(),
+            max_in_flight: Default::default(),
+        };
+
+        let input_objs = vec![InputObjectKind::SharedMoveObject {
+            id,
+            initial_shared_version: SequenceNumber::from_u64(1),
+            mutable: true,
+        }];
+        let changes = get_balance_changes_from_effect(
+            &provider,
+            &effects(&[&before], &[&after], None),
+            input_objs,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(changes.is_empty());
+        assert_eq!(fetches.load(Ordering::SeqCst), 0);
+
+        // Callers passing object refs directly still get no change for shared coins.
+        let changes = get_balance_changes(
+            &provider,
+            &[(id, before.version(), None)],
+            &[(id, after.version(), None)],
+        )
+        .await
+        .unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mutated_consensus_coin_balance_changes() {
+        let owner = SuiAddress::random_for_testing_only();
+        let recipient = SuiAddress::random_for_testing_only();
+        let id = ObjectID::random();
+        let (before, after) = (
+            consensus_coin(id, 1, owner, 100),
+            consensus_coin(id, 2, owner, 70),
+        );
+        let to_recipient = gas_coin(ObjectID::random(), 2, recipient, 30);
+        let provider =
+            MapObjectProvider::new([before.clone(), after.clone(), to_recipient.clone()]);
+
+        // Consensus-owned coins are passed as shared inputs, but still hold a balance.
+        let input_objs = vec![InputObjectKind::SharedMoveObject {
+            id,
+            initial_shared_version: SequenceNumber::from_u64(1),
+            mutable: true,
+        }];
+        let changes = get_balance_changes_from_effect(
+            &provider,
+            &effects(&[&before], &[&after, &to_recipient], None),
+            input_objs,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(changes.contains(&BalanceChange {
+            owner: before.owner.clone(),
+            coin_type: GAS::type_tag(),
+            amount: -30,
+        }));
+        assert!(changes.contains(&BalanceChange {
+            owner: Owner::AddressOwner(recipient),
+            coin_type: GAS::type_tag(),
+            amount: 30,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_mocked_coin_handling() {
+        let sender = SuiAddress::random_for_testing_only();
+        let recipient = SuiAddress::random_for_testing_only();
+        let gas = gas_coin(ObjectID::random(), 1, sender, 1000);
+        let gas_after = gas_coin(gas.id(), 2, sender, 900);
+        let coin = gas_coin(ObjectID::random(), 1, sender, 500);
+        let coin_after = gas_coin(coin.id(), 2, recipient, 500);
+        // The mocked gas coin is unknown to the provider, as it would be during a dry run.
+        let provider = MapObjectProvider::new([coin.clone(), coin_after.clone()]);
+        let tx_effects = effects(&[&gas, &coin], &[&gas_after, &coin_after], Some(gas.id()));
+
+        // Mocked coin used as gas: only the real coin is fetched and reported.
+        let changes = get_balance_changes_from_effect(
+            &provider,
+            &tx_effects,
+            owned_inputs(&[&gas, &coin]),
+            Some(gas.id()),
+        )
+        .await
+        .unwrap();
+        let amounts: BTreeMap<_, _> = changes.into_iter().map(|c| (c.owner, c.amount)).collect();
+        assert_eq!(
+            amounts,
+            BTreeMap::from([
+                (Owner::AddressOwner(sender), -500),
+                (Owner::AddressOwner(recipient), 500),
+            ])
+        );
+        let coin_changes = get_coin_object_changes_from_effect(
+            &provider,
+            &tx_effects,
+            owned_inputs(&[&gas, &coin]),
+            Some(gas.id()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            coin_changes.iter().map(|c| c.object_id).collect::<Vec<_>>(),
+            vec![coin.id()]
+        );
+
+        // Mocked regular coin: the gas coin is reported and the mocked one is not.
+        let provider = MapObjectProvider::new([gas.clone(), gas_after.clone()]);
+        let changes = get_owner_balance_changes_from_effect(
+            &provider,
+            &tx_effects,
+            owned_inputs(&[&gas, &coin]),
+            Some(coin.id()),
+            sender,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].amount, -100);
+
+        // No mocked coin: every coin must be known to the provider.
+        let provider = MapObjectProvider::new([gas.clone(), gas_after, coin.clone(), coin_after]);
+        let changes = get_balance_changes_from_effect(
+            &provider,
+            &tx_effects,
+            owned_inputs(&[&gas, &coin]),
+            None,
+        )
+        .await
+        .unwrap();
+        let amounts: BTreeMap<_, _> = changes.into_iter().map(|c| (c.owner, c.amount)).collect();
+        assert_eq!(
+            amounts,
+            BTreeMap::from([
+                (Owner::AddressOwner(sender), -600),
+                (Owner::AddressOwner(recipient), 500),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_transaction_charges_mocked_gas() {
+        let sender = SuiAddress::random_for_testing_only();
+        let gas = ObjectID::random();
+        let computation_cost = 100;
+        let tx_effects = TransactionEffects::new_from_execution_v2(
+            ExecutionStatus::new_failure(ExecutionFailureStatus::InsufficientGas, None),
+            0,
+            GasCostSummary::new(computation_cost, 0, 0, 0),
+            vec![],
+            BTreeSet::new(),
+            TransactionDigest::random(),
+            SequenceNumber::from_u64(2),
+            BTreeMap::from([(
+                gas,
+                EffectsObjectChange::new(
+                    Some((
+                        (SequenceNumber::from_u64(1), ObjectDigest::random()),
+                        Owner::AddressOwner(sender),
+                    )),
+                    Some(&gas_coin(gas, 2, sender, 900)),
+                    false,
+                    false,
+                ),
+            )]),
+            Some(gas),
+            None,
+            vec![],
+        );
+
+        // Nothing is fetched, so the provider never has to know the mocked coin.
+        let changes = get_balance_changes_from_effect(
+            &MapObjectProvider::new([]),
+            &tx_effects,
+            vec![],
+            Some(gas),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            changes,
+            vec![BalanceChange {
+                owner: Owner::AddressOwner(sender),
+                coin_type: GAS::type_tag(),
+                amount: -(computation_cost as i128),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreconciled_balance_changes() {
+        let sender = SuiAddress::random_for_testing_only();
+        let recipient = SuiAddress::random_for_testing_only();
+        let before = gas_coin(ObjectID::random(), 1, sender, 1000);
+        let after = gas_coin(before.id(), 2, sender, 600);
+        let received = gas_coin(ObjectID::random(), 2, recipient, 400);
+        let provider = MapObjectProvider::new([before.clone(), after.clone(), received.clone()]);
+        let tx_effects = effects(&[&before], &[&after, &received], Some(before.id()));
+
+        let mut changes =
+            get_balance_changes_from_effect(&provider, &tx_effects, owned_inputs(&[&before]), None)
+                .await
+                .unwrap();
+        assert!(unreconciled_balance_changes(&tx_effects, &changes).is_empty());
+
+        // Coins appearing out of nowhere, as with a mint.
+        let minted: TypeTag = "0x42::token::TOKEN".parse().unwrap();
+        changes.push(BalanceChange {
+            owner: Owner::AddressOwner(recipient),
+            coin_type: minted.clone(),
+            amount: 5,
+        });
+        assert_eq!(
+            unreconciled_balance_changes(&tx_effects, &changes),
+            vec![(minted, 5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_detect_coin_operations() {
+        let owner = SuiAddress::random_for_testing_only();
+        let coin = ObjectID::random();
+        let others: Vec<_> = (0..3).map(|_| ObjectID::random()).collect();
+
+        // 1 -> 3: the coin keeps 10 and three new coins of 30 are split off it.
+        let before = gas_coin(coin, 1, owner, 100);
+        let after = gas_coin(coin, 2, owner, 10);
+        let created: Vec<_> = others
+            .iter()
+            .map(|id| gas_coin(*id, 2, owner, 30))
+            .collect();
+        let outputs: Vec<_> = [&after].into_iter().chain(&created).collect();
+        let provider = MapObjectProvider::new(
+            [before.clone(), after.clone()]
+                .into_iter()
+                .chain(created.clone()),
+        );
+        let changes = get_coin_object_changes_from_effect(
+            &provider,
+            &effects(&[&before], &outputs, None),
+            owned_inputs(&[&before]),
+            None,
+        )
+        .await
+        .unwrap();
+        let mut sorted = others.clone();
+        sorted.sort();
+        assert_eq!(
+            detect_coin_operations(&changes),
+            vec![CoinOperation::Split {
+                coin_type: GAS::type_tag(),
+                source: coin,
+                created: sorted.clone(),
+            }]
+        );
+
+        // 3 -> 1: three coins of 30 are merged into the coin, which grows to 100.
+        let before = gas_coin(coin, 1, owner, 10);
+        let after = gas_coin(coin, 2, owner, 100);
+        let merged: Vec<_> = others
+            .iter()
+            .map(|id| gas_coin(*id, 1, owner, 30))
+            .collect();
+        let inputs: Vec<_> = [&before].into_iter().chain(&merged).collect();
+        let provider = MapObjectProvider::new(
+            [before.clone(), after.clone()]
+                .into_iter()
+                .chain(merged.clone()),
+        );
+        let changes = get_coin_object_changes_from_effect(
+            &provider,
+            &effects(&inputs, &[&after], None),
+            owned_inputs(&inputs),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            detect_coin_operations(&changes),
+            vec![CoinOperation::Merge {
+                coin_type: GAS::type_tag(),
+                target: coin,
+                merged: sorted,
+            }]
+        );
+
+        // A plain transfer is neither.
+        let changes = vec![CoinObjectChange {
+            object_id: coin,
+            coin_type: GAS::type_tag(),
+            input: Some((Owner::AddressOwner(owner), 10)),
+            output: Some((
+                Owner::AddressOwner(SuiAddress::random_for_testing_only()),
+                10,
+            )),
+        }];
+        assert!(detect_coin_operations(&changes).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_map_object_provider() {
+        let owner = SuiAddress::random_for_testing_only();
+        let id = ObjectID::random();
+        let provider =
+            MapObjectProvider::new([gas_coin(id, 1, owner, 10), gas_coin(id, 3, owner, 30)]);
+
+        let v3 = SequenceNumber::from_u64(3);
+        assert_eq!(provider.get_object(&id, &v3).await.unwrap().version(), v3);
+        assert!(provider
+            .get_object(&id, &SequenceNumber::from_u64(2))
+            .await
+            .is_err());
+
+        for (query, expected) in [
+            (2, Some(SequenceNumber::from_u64(1))),
+            (5, Some(v3)),
+            (0, None),
+        ] {
+            let found = provider
+                .find_object_lt_or_eq_version(&id, &SequenceNumber::from_u64(query))
+                .await
+                .unwrap();
+            assert_eq!(found.map(|o| o.version()), expected);
+        }
+    }
+}